@@ -3,6 +3,8 @@ use std::{net::SocketAddr, sync::Arc};
 use larlis_core::actor;
 use tokio::{net::UdpSocket, spawn};
 
+pub mod reliable;
+
 pub struct In<A> {
     pub socket: Arc<UdpSocket>,
     pub state: A,
@@ -0,0 +1,325 @@
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, HashMap},
+    hash::{Hash, Hasher},
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use larlis_core::actor;
+use neat_core::{message::Timeout, Drive, State as NeatState, Wire};
+use neat_tokio::timer::{Deadline, TimerService};
+use tokio::{net::UdpSocket, select, spawn};
+
+const INITIAL_RTO: Duration = Duration::from_millis(100);
+const MAX_RTO: Duration = Duration::from_secs(3);
+
+type Seq = u32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Data,
+    Ack,
+}
+
+#[derive(Default)]
+struct PeerState {
+    next_send_seq: Seq,
+    next_expect_seq: Seq,
+    unacked: BTreeMap<Seq, Vec<u8>>,
+    reorder: BTreeMap<Seq, Vec<u8>>,
+}
+
+impl PeerState {
+    /// The cumulative ack to report for this peer, or `None` if we have not
+    /// received anything from it yet -- distinct from "acks everything up to
+    /// and including sequence 0", which `next_expect_seq == 0` would
+    /// otherwise be indistinguishable from under a wrapping-subtraction
+    /// sentinel.
+    fn ack(&self) -> Option<Seq> {
+        (self.next_expect_seq > 0).then(|| self.next_expect_seq - 1)
+    }
+}
+
+/// Identifies one in-flight unacked packet for `neat_tokio::timer::TimerService`'s
+/// retransmission schedule. Equality/ordering/hashing only consider
+/// `(peer, seq)`, so a `Reset` carrying a doubled `rto` still refers to the
+/// same scheduled timeout rather than a distinct one.
+#[derive(Debug, Clone)]
+struct RetransmitKey {
+    peer: SocketAddr,
+    seq: Seq,
+    rto: Duration,
+}
+
+impl PartialEq for RetransmitKey {
+    fn eq(&self, other: &Self) -> bool {
+        (self.peer, self.seq) == (other.peer, other.seq)
+    }
+}
+
+impl Eq for RetransmitKey {}
+
+impl Hash for RetransmitKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.peer.hash(state);
+        self.seq.hash(state);
+    }
+}
+
+impl PartialOrd for RetransmitKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for RetransmitKey {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.peer, self.seq).cmp(&(other.peer, other.seq))
+    }
+}
+
+impl Deadline for RetransmitKey {
+    fn duration(&self) -> Duration {
+        self.rto
+    }
+}
+
+type TimerControl = Arc<Mutex<Box<dyn FnMut(Timeout<RetransmitKey>) + Send>>>;
+
+/// Per-peer sliding-window ARQ layer over the UDP [`In`](crate::In)/[`Out`](crate::Out)
+/// actors, giving TCP-like delivery guarantees (in-order, no duplicates, no
+/// silent loss) while keeping UDP's connectionless addressing.
+///
+/// Every outbound payload gets a monotonically increasing sequence number
+/// and sits in an unacked buffer until the peer cumulatively acks it.
+/// Retransmission is driven through `neat_tokio::timer::TimerService`: each
+/// send `Set`s a timeout keyed by `(peer, seq)`, a fired-but-still-unacked
+/// timeout resends and `Reset`s itself with a doubled `rto` (capped at
+/// [`MAX_RTO`]), and a fresh cumulative ack `Unset`s every timeout it
+/// covers. Inbound out-of-order packets are buffered in a small reorder map
+/// and delivered to the wrapped application state only once contiguous;
+/// duplicate/old sequence numbers are dropped after re-acking.
+pub struct Reliable<A> {
+    socket: Arc<UdpSocket>,
+    state: A,
+    peers: Arc<Mutex<HashMap<SocketAddr, PeerState>>>,
+    fired: Drive<RetransmitKey>,
+    timer: TimerControl,
+}
+
+impl<A> Reliable<A> {
+    pub fn new(socket: Arc<UdpSocket>, state: A) -> Self {
+        let fired_wire = Wire::default();
+        let fired_sink = fired_wire.state();
+        let fired = Drive::from(fired_wire);
+
+        let control_wire = Wire::default();
+        let mut control_sink = control_wire.state();
+        let control = Drive::from(control_wire);
+        spawn(async move { TimerService::new(fired_sink).run(control).await });
+
+        let timer: TimerControl = Arc::new(Mutex::new(Box::new(move |message| {
+            control_sink.update(message)
+        })));
+
+        Self {
+            socket,
+            state,
+            peers: Default::default(),
+            fired,
+            timer,
+        }
+    }
+
+    /// Egress handle mirroring [`Out`](crate::Out): reliably sends payloads
+    /// to a peer, queuing them for retransmission until acked.
+    pub fn out_state(&self) -> ReliableOut {
+        ReliableOut {
+            socket: self.socket.clone(),
+            peers: self.peers.clone(),
+            timer: self.timer.clone(),
+        }
+    }
+}
+
+pub struct ReliableOut {
+    socket: Arc<UdpSocket>,
+    peers: Arc<Mutex<HashMap<SocketAddr, PeerState>>>,
+    timer: TimerControl,
+}
+
+impl actor::State<'_> for ReliableOut {
+    type Message = (SocketAddr, Vec<u8>);
+
+    fn update(&mut self, message: Self::Message) {
+        let (remote, payload) = message;
+        let mut peers = self.peers.lock().unwrap();
+        let peer = peers.entry(remote).or_default();
+        let seq = peer.next_send_seq;
+        peer.next_send_seq += 1;
+        let ack = peer.ack();
+        send_packet(&self.socket, remote, Kind::Data, seq, ack, &payload);
+        peer.unacked.insert(seq, payload);
+        drop(peers);
+        (self.timer.lock().unwrap())(Timeout::Set(RetransmitKey {
+            peer: remote,
+            seq,
+            rto: INITIAL_RTO,
+        }));
+    }
+}
+
+impl<A> Reliable<A>
+where
+    A: for<'a> actor::State<'a, Message = (SocketAddr, &'a [u8])>,
+{
+    pub async fn start(&mut self) {
+        let mut buf = vec![0; 65536];
+        loop {
+            select! {
+                received = self.socket.recv_from(&mut buf) => {
+                    let (len, remote) = received.unwrap();
+                    self.on_packet(remote, &buf[..len]);
+                }
+                key = self.fired.recv() => {
+                    let Some(key) = key else {
+                        continue;
+                    };
+                    self.on_retransmit_fired(key);
+                }
+            }
+        }
+    }
+
+    fn on_packet(&mut self, remote: SocketAddr, packet: &[u8]) {
+        let Some((kind, seq, ack, payload)) = decode_packet(packet) else {
+            return;
+        };
+        let mut peers = self.peers.lock().unwrap();
+        let peer = peers.entry(remote).or_default();
+        // a peer that has not received anything yet reports `ack = None`,
+        // not "acks everything" -- only prune once it has actually acked
+        let newly_acked = if let Some(ack) = ack {
+            let newly_acked = Vec::from_iter(peer.unacked.range(..=ack).map(|(&seq, _)| seq));
+            for seq in &newly_acked {
+                peer.unacked.remove(seq);
+            }
+            newly_acked
+        } else {
+            Vec::new()
+        };
+
+        if kind == Kind::Ack {
+            drop(peers);
+            self.unset_timers(remote, newly_acked);
+            return;
+        }
+
+        if seq < peer.next_expect_seq {
+            // duplicate/old, re-ack so the peer stops retransmitting it
+            let ack = peer.ack();
+            drop(peers);
+            self.unset_timers(remote, newly_acked);
+            send_packet(&self.socket, remote, Kind::Ack, 0, ack, &[]);
+            return;
+        }
+        if seq > peer.next_expect_seq {
+            peer.reorder.insert(seq, payload.to_vec());
+            let ack = peer.ack();
+            drop(peers);
+            self.unset_timers(remote, newly_acked);
+            send_packet(&self.socket, remote, Kind::Ack, 0, ack, &[]);
+            return;
+        }
+
+        let mut deliver = vec![payload.to_vec()];
+        peer.next_expect_seq += 1;
+        while let Some(next) = peer.reorder.remove(&peer.next_expect_seq) {
+            deliver.push(next);
+            peer.next_expect_seq += 1;
+        }
+        let ack = peer.ack();
+        drop(peers);
+        self.unset_timers(remote, newly_acked);
+        send_packet(&self.socket, remote, Kind::Ack, 0, ack, &[]);
+        for payload in deliver {
+            self.state.update((remote, &payload));
+        }
+    }
+
+    fn unset_timers(&self, remote: SocketAddr, seqs: Vec<Seq>) {
+        if seqs.is_empty() {
+            return;
+        }
+        let mut timer = self.timer.lock().unwrap();
+        for seq in seqs {
+            timer(Timeout::Unset(RetransmitKey {
+                peer: remote,
+                seq,
+                rto: INITIAL_RTO,
+            }));
+        }
+    }
+
+    fn on_retransmit_fired(&mut self, key: RetransmitKey) {
+        let mut peers = self.peers.lock().unwrap();
+        let Some(peer) = peers.get_mut(&key.peer) else {
+            return;
+        };
+        let Some(payload) = peer.unacked.get(&key.seq).cloned() else {
+            // acked in the meantime; `Unset` already dropped this schedule
+            return;
+        };
+        let ack = peer.ack();
+        drop(peers);
+        send_packet(&self.socket, key.peer, Kind::Data, key.seq, ack, &payload);
+        let rto = (key.rto * 2).min(MAX_RTO);
+        (self.timer.lock().unwrap())(Timeout::Reset(RetransmitKey {
+            peer: key.peer,
+            seq: key.seq,
+            rto,
+        }));
+    }
+}
+
+fn send_packet(
+    socket: &Arc<UdpSocket>,
+    remote: SocketAddr,
+    kind: Kind,
+    seq: Seq,
+    ack: Option<Seq>,
+    payload: &[u8],
+) {
+    let mut packet = Vec::with_capacity(10 + payload.len());
+    packet.push(match kind {
+        Kind::Data => 0,
+        Kind::Ack => 1,
+    });
+    packet.push(ack.is_some() as u8);
+    packet.extend_from_slice(&seq.to_be_bytes());
+    packet.extend_from_slice(&ack.unwrap_or_default().to_be_bytes());
+    packet.extend_from_slice(payload);
+    let socket = socket.clone();
+    spawn(async move {
+        socket.send_to(&packet, remote).await.unwrap();
+    });
+}
+
+fn decode_packet(packet: &[u8]) -> Option<(Kind, Seq, Option<Seq>, &[u8])> {
+    let (&kind, rest) = packet.split_first()?;
+    let kind = match kind {
+        0 => Kind::Data,
+        1 => Kind::Ack,
+        _ => return None,
+    };
+    let (&has_ack, rest) = rest.split_first()?;
+    if rest.len() < 8 {
+        return None;
+    }
+    let seq = Seq::from_be_bytes(rest[..4].try_into().unwrap());
+    let ack = Seq::from_be_bytes(rest[4..8].try_into().unwrap());
+    let ack = (has_ack != 0).then_some(ack);
+    Some((kind, seq, ack, &rest[8..]))
+}
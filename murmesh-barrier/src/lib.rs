@@ -62,6 +62,16 @@ where
     }
 }
 
+// TODO back this with `neat_tokio::Pool` instead of a hand-managed
+// `murmesh_tcp::Connection` + `Dispatch::insert_state`, so callers stop
+// managing connection lifecycles manually. Blocked on `murmesh-barrier`
+// actually depending on `murmesh_core`/`murmesh_tcp`/`murmesh_bincode`
+// (none of which exist in this tree yet) while `Pool` was built against
+// `neat_core`/`neat_tokio` -- porting this module onto the `neat_*` stack
+// is its own follow-up, not a drop-in swap. Unresolved, not just deferred:
+// this function does not yet satisfy the "make Pool the default backing
+// for use_barrier" deliverable, and needs an explicit maintainer decision
+// to either greenlight the `neat_*` port or split it into its own request.
 pub async fn use_barrier<M>(addr: SocketAddr, service: SocketAddr, payload: M) -> Message<M>
 where
     M: Serialize + DeserializeOwned + Send + 'static,
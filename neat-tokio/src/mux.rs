@@ -0,0 +1,247 @@
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use neat_core::{Drive, State, Wire};
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    select,
+    sync::mpsc,
+};
+
+use crate::tcp::Disconnected;
+
+// alternative design: one `TcpStream` per substream instead of framing on top
+// of a single stream, but that defeats the point of sharing one connection
+// (and one set of TCP/TLS handshakes) across many logical channels
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FrameType {
+    Open,
+    Data,
+    Close,
+}
+
+impl FrameType {
+    fn to_u8(self) -> u8 {
+        match self {
+            Self::Open => 0,
+            Self::Data => 1,
+            Self::Close => 2,
+        }
+    }
+
+    fn from_u8(byte: u8) -> Option<Self> {
+        match byte {
+            0 => Some(Self::Open),
+            1 => Some(Self::Data),
+            2 => Some(Self::Close),
+            _ => None,
+        }
+    }
+}
+
+struct Frame {
+    stream_id: u32,
+    frame_type: FrameType,
+    payload: Vec<u8>,
+}
+
+/// A newly accepted substream, handed to the `accept` sink of
+/// [`MuxConnection::start`] when the peer opens a stream id we have not seen
+/// before.
+pub struct Accepted {
+    pub stream_id: u32,
+    pub out: MuxOutState,
+    pub inbound: Drive<Vec<u8>>,
+}
+
+/// Egress handle for one substream, as returned by `open()` or handed out in
+/// [`Accepted`]; there is one handle per `stream_id`, and dropping it closes
+/// that substream. Frames from every substream are multiplexed onto the one
+/// underlying socket by `MuxConnection::start`.
+pub struct MuxOutState {
+    stream_id: u32,
+    sender: mpsc::UnboundedSender<Frame>,
+    inbound: Arc<Mutex<HashMap<u32, InboundSink>>>,
+}
+
+impl State<Vec<u8>> for MuxOutState {
+    fn update(&mut self, message: Vec<u8>) {
+        let _ = self.sender.send(Frame {
+            stream_id: self.stream_id,
+            frame_type: FrameType::Data,
+            payload: message,
+        });
+    }
+}
+
+impl Drop for MuxOutState {
+    fn drop(&mut self) {
+        // remove our own inbound entry too -- otherwise a local close whose
+        // peer never sends back a `Close` frame of its own leaks this
+        // substream's boxed sink closure for the life of the connection
+        self.inbound.lock().unwrap().remove(&self.stream_id);
+        let _ = self.sender.send(Frame {
+            stream_id: self.stream_id,
+            frame_type: FrameType::Close,
+            payload: Vec::new(),
+        });
+    }
+}
+
+type InboundSink = Box<dyn FnMut(Vec<u8>) + Send>;
+
+/// Yamux/mplex-style substream layer on top of a single `murmesh_tcp`-style
+/// connection.
+///
+/// Wire framing is `{ stream_id: u32, frame_type: u8, len: u32, payload }`.
+/// Stream ids opened by the dialing side are odd, those opened by the
+/// accepting side are even, so both ends can allocate ids without colliding.
+pub struct MuxConnection<T> {
+    remote_addr: SocketAddr,
+    stream: T,
+    next_local_id: AtomicU32,
+    out_tx: mpsc::UnboundedSender<Frame>,
+    out_rx: Option<mpsc::UnboundedReceiver<Frame>>,
+    inbound: Arc<Mutex<HashMap<u32, InboundSink>>>,
+}
+
+impl<T> MuxConnection<T> {
+    fn new(stream: T, remote_addr: SocketAddr, first_local_id: u32) -> Self {
+        let (out_tx, out_rx) = mpsc::unbounded_channel();
+        Self {
+            stream,
+            remote_addr,
+            next_local_id: AtomicU32::new(first_local_id),
+            out_tx,
+            out_rx: Some(out_rx),
+            inbound: Default::default(),
+        }
+    }
+
+    /// For the side that dialed the underlying connection. Allocates odd
+    /// local stream ids, mirroring [`MuxConnection::accept`]'s even ones, so
+    /// both sides calling `open()` (e.g. a control channel plus a bulk
+    /// channel) can't generate colliding ids.
+    pub fn connect(stream: T, remote_addr: SocketAddr) -> Self {
+        Self::new(stream, remote_addr, 1)
+    }
+
+    /// For the side that accepted the underlying connection. See
+    /// [`MuxConnection::connect`].
+    pub fn accept(stream: T, remote_addr: SocketAddr) -> Self {
+        Self::new(stream, remote_addr, 2)
+    }
+
+    fn out_state(&self, stream_id: u32) -> MuxOutState {
+        MuxOutState {
+            stream_id,
+            sender: self.out_tx.clone(),
+            inbound: self.inbound.clone(),
+        }
+    }
+
+    fn register_inbound(&self, stream_id: u32) -> Drive<Vec<u8>> {
+        let wire = Wire::default();
+        let mut sink = wire.state();
+        let drive = Drive::from(wire);
+        self.inbound
+            .lock()
+            .unwrap()
+            .insert(stream_id, Box::new(move |payload| sink.update(payload)));
+        drive
+    }
+
+    /// Opens a locally-initiated substream, returning an egress handle for
+    /// writing to it and a `Drive` yielding the bytes received on it.
+    pub fn open(&self) -> (MuxOutState, Drive<Vec<u8>>) {
+        let stream_id = self.next_local_id.fetch_add(2, Ordering::Relaxed);
+        let inbound = self.register_inbound(stream_id);
+        let _ = self.out_tx.send(Frame {
+            stream_id,
+            frame_type: FrameType::Open,
+            payload: Vec::new(),
+        });
+        (self.out_state(stream_id), inbound)
+    }
+}
+
+impl<T> MuxConnection<T>
+where
+    T: AsyncRead + AsyncWrite + Unpin,
+{
+    pub async fn start(
+        &mut self,
+        mut accept: impl State<Accepted>,
+        mut disconnected: impl State<Disconnected>,
+    ) {
+        let mut out_rx = self.out_rx.take().expect("start called once");
+        let mut buf = vec![0; 65536];
+        loop {
+            select! {
+                stream_id = self.stream.read_u32() => {
+                    let Ok(stream_id) = stream_id else {
+                        break;
+                    };
+                    let Ok(frame_type) = self.stream.read_u8().await else {
+                        break;
+                    };
+                    let Some(frame_type) = FrameType::from_u8(frame_type) else {
+                        break;
+                    };
+                    let Ok(len) = self.stream.read_u32().await else {
+                        break;
+                    };
+                    if len as usize > buf.len() {
+                        // peer claims a frame larger than we'll ever buffer
+                        break;
+                    }
+                    if self.stream.read_exact(&mut buf[..len as _]).await.is_err() {
+                        break;
+                    }
+                    match frame_type {
+                        FrameType::Open => {
+                            if !self.inbound.lock().unwrap().contains_key(&stream_id) {
+                                let inbound = self.register_inbound(stream_id);
+                                accept.update(Accepted {
+                                    stream_id,
+                                    out: self.out_state(stream_id),
+                                    inbound,
+                                });
+                            }
+                        }
+                        FrameType::Data => {
+                            if let Some(sink) = self.inbound.lock().unwrap().get_mut(&stream_id) {
+                                sink(buf[..len as _].to_vec());
+                            }
+                        }
+                        FrameType::Close => {
+                            self.inbound.lock().unwrap().remove(&stream_id);
+                        }
+                    }
+                }
+                frame = out_rx.recv() => {
+                    let Some(frame) = frame else {
+                        // every `MuxOutState` (and `MuxConnection` itself) dropped
+                        break;
+                    };
+                    if self.stream.write_u32(frame.stream_id).await.is_err()
+                        || self.stream.write_u8(frame.frame_type.to_u8()).await.is_err()
+                        || self.stream.write_u32(frame.payload.len() as _).await.is_err()
+                        || self.stream.write_all(&frame.payload).await.is_err()
+                        || self.stream.flush().await.is_err()
+                    {
+                        break;
+                    }
+                }
+            }
+        }
+        disconnected.update(Disconnected(self.remote_addr))
+    }
+}
@@ -0,0 +1,150 @@
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use neat_core::{message::Transport, Drive, State, Wire};
+use tokio::{
+    spawn,
+    time::{interval, Instant},
+};
+
+use crate::tcp::{Connection, Control, Disconnected};
+
+// alternative design: have `GeneralConnection` expose its own `out_state()`
+// and keep a `Dispatch` of those (what `murmesh_barrier::use_barrier` does by
+// hand with `murmesh_tcp`); instead `Pool` wires up its own `Wire` per
+// connection so it only depends on `Connection::start`'s existing
+// `Drive`-taking signature
+
+type InboundSink = Box<dyn FnMut(Vec<u8>) + Send>;
+type ControlSink = Box<dyn FnMut(Control) + Send>;
+
+struct Entry {
+    sink: InboundSink,
+    control: ControlSink,
+    last_used: Instant,
+    // distinguishes this dial from a later one that redialed the same addr
+    // after this one disconnected but before `EvictOnDisconnect` ran, so a
+    // stale disconnect callback can't evict the wrong (live) connection
+    generation: u64,
+}
+
+/// Lazily dials and reuses `neat_tokio::tcp` connections, so callers can
+/// address any peer without first establishing and registering a
+/// connection by hand.
+///
+/// Sits behind a `State<Transport<Vec<u8>>>`: a message to an address with
+/// no live connection dials it, spawns `Connection::start`, and queues the
+/// payload on the connection's own egress channel (which buffers it until
+/// the dial completes, so nothing is dropped while connecting). Connections
+/// are evicted from the pool as soon as they disconnect, so the next
+/// message to that address redials. An idle timer sends `Control::Close` to
+/// connections that have gone unused for `max_idle` before evicting them.
+pub struct Pool<F> {
+    local_addr: SocketAddr,
+    inbound_state_factory: F,
+    connections: Arc<Mutex<HashMap<SocketAddr, Entry>>>,
+    next_generation: Arc<AtomicU64>,
+}
+
+impl<F, S> Pool<F>
+where
+    F: FnMut() -> S,
+    S: for<'m> State<Transport<&'m [u8]>> + Send + 'static,
+{
+    pub fn new(local_addr: SocketAddr, max_idle: Duration, inbound_state_factory: F) -> Self {
+        let connections = Arc::<Mutex<HashMap<_, _>>>::default();
+        spawn(reap_idle(connections.clone(), max_idle));
+        Self {
+            local_addr,
+            inbound_state_factory,
+            connections,
+            next_generation: Default::default(),
+        }
+    }
+}
+
+impl<F, S> State<Transport<Vec<u8>>> for Pool<F>
+where
+    F: FnMut() -> S,
+    S: for<'m> State<Transport<&'m [u8]>> + Send + 'static,
+{
+    fn update(&mut self, message: Transport<Vec<u8>>) {
+        let (addr, payload) = message;
+        let mut connections = self.connections.lock().unwrap();
+        let entry = connections.entry(addr).or_insert_with(|| {
+            let wire = Wire::default();
+            let mut out = wire.state();
+            let drive = Drive::from(wire);
+            let control_wire = Wire::default();
+            let mut control_out = control_wire.state();
+            let control = Drive::from(control_wire);
+            let local_addr = self.local_addr;
+            let inbound_state = (self.inbound_state_factory)();
+            let generation = self.next_generation.fetch_add(1, Ordering::Relaxed);
+            let disconnected = EvictOnDisconnect {
+                addr,
+                generation,
+                connections: self.connections.clone(),
+            };
+            spawn(async move {
+                let mut connection = Connection::connect(local_addr, addr).await;
+                connection
+                    .start(drive, control, inbound_state, disconnected)
+                    .await
+            });
+            Entry {
+                sink: Box::new(move |payload| out.update(payload)),
+                control: Box::new(move |control| control_out.update(control)),
+                last_used: Instant::now(),
+                generation,
+            }
+        });
+        entry.last_used = Instant::now();
+        (entry.sink)(payload);
+    }
+}
+
+struct EvictOnDisconnect {
+    addr: SocketAddr,
+    generation: u64,
+    connections: Arc<Mutex<HashMap<SocketAddr, Entry>>>,
+}
+
+impl State<Disconnected> for EvictOnDisconnect {
+    fn update(&mut self, Disconnected(addr): Disconnected) {
+        let mut connections = self.connections.lock().unwrap();
+        // only evict the connection this callback belongs to -- a redial
+        // may already have replaced it with a newer, live one by the time
+        // this (possibly stale) disconnect notification arrives
+        if connections.get(&addr).is_some_and(|entry| entry.generation == self.generation) {
+            connections.remove(&addr);
+        }
+    }
+}
+
+async fn reap_idle(connections: Arc<Mutex<HashMap<SocketAddr, Entry>>>, max_idle: Duration) {
+    let mut tick = interval(max_idle / 2);
+    loop {
+        tick.tick().await;
+        let now = Instant::now();
+        let mut connections = connections.lock().unwrap();
+        let idle = Vec::from_iter(
+            connections
+                .iter()
+                .filter(|(_, entry)| now.duration_since(entry.last_used) >= max_idle)
+                .map(|(&addr, _)| addr),
+        );
+        for addr in idle {
+            if let Some(mut entry) = connections.remove(&addr) {
+                (entry.control)(Control::Close);
+            }
+        }
+    }
+}
@@ -47,10 +47,28 @@ impl Connection {
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Disconnected(pub SocketAddr);
 
+/// Explicit control of the local half of a connection, driven alongside the
+/// egress `Drive` in [`GeneralConnection::start`].
+///
+/// `CloseWrite` is a true half-close: it stops reading from `drive` and
+/// shuts down the write half to signal EOF to the peer, while inbound
+/// frames keep being read and delivered, so a caller can finish draining
+/// acks for messages it already sent. `Close` tears down both directions
+/// immediately. `Flush` is for latency-sensitive callers that need queued
+/// writes on the wire before e.g. awaiting a reply, without tearing
+/// anything down.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Control {
+    Flush,
+    CloseWrite,
+    Close,
+}
+
 impl<T> GeneralConnection<T> {
     pub async fn start(
         &mut self,
         mut drive: Drive<Vec<u8>>,
+        mut control: Drive<Control>,
         mut state: impl for<'m> State<Transport<&'m [u8]>>,
         mut disconnected: impl State<Disconnected>,
     ) where
@@ -59,6 +77,8 @@ impl<T> GeneralConnection<T> {
     {
         let mut buf = vec![0; 65536]; //
         let mut local_close = false;
+        let mut write_closed = false;
+        let mut control_closed = false;
         loop {
             select! {
                 len = self.stream.read_u32() => {
@@ -66,13 +86,17 @@ impl<T> GeneralConnection<T> {
                         // broken connection
                         break;
                     };
+                    if len as usize > buf.len() {
+                        // peer claims a frame larger than we'll ever buffer
+                        break;
+                    }
                     if self.stream.read_exact(&mut buf[..len as _]).await.is_err() {
                         // broken connection
                         break;
                     }
                     state.update((self.remote_addr, &buf[..len as _]));
                 }
-                message = drive.recv(), if !local_close => {
+                message = drive.recv(), if !local_close && !write_closed => {
                     let Some(message) = message else {
                         local_close = true;
                         continue;
@@ -85,6 +109,29 @@ impl<T> GeneralConnection<T> {
                         break;
                     }
                 }
+                control = control.recv(), if !control_closed => {
+                    match control {
+                        None => {
+                            // no sender kept alive, e.g. a caller that does not
+                            // use the `Control` feature at all -- stop polling
+                            // this arm instead of spinning on it forever
+                            control_closed = true;
+                        }
+                        Some(Control::Flush) if self.stream.flush().await.is_err() => {
+                            // broken connection
+                            break;
+                        }
+                        Some(Control::Flush) => {}
+                        Some(Control::CloseWrite) => {
+                            write_closed = true;
+                            if self.stream.shutdown().await.is_err() {
+                                // broken connection
+                                break;
+                            }
+                        }
+                        Some(Control::Close) => break,
+                    }
+                }
             }
         }
         disconnected.update(Disconnected(self.remote_addr))
@@ -0,0 +1,118 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+    hash::Hash,
+    time::Duration,
+};
+
+use neat_core::{message::Timeout, Drive, State};
+use tokio::{
+    select,
+    time::{sleep_until, Instant},
+};
+
+/// A timeout payload that knows how long it should wait before firing.
+///
+/// Implementors are typically small enum/struct keys that also double as the
+/// identity used to `Set`/`Reset`/`Unset` them, so `T` additionally needs to
+/// be `Eq + Hash + Ord + Clone` to serve as both a `HashMap` key and a
+/// `BinaryHeap` entry.
+pub trait Deadline {
+    fn duration(&self) -> Duration;
+}
+
+/// Owns wall-clock scheduling for [`Timeout`] and fires expired timeouts into
+/// `egress`.
+///
+/// This is the `State<Timeout<T>>` the doc comment on [`Timeout`]
+/// promises: a timeout delivered after it was `Unset` is never observed by
+/// `egress`, and a `Reset` timeout is delivered, if at all, only at its
+/// latest deadline.
+///
+/// Implemented with a `HashMap<T, (Instant, generation)>` recording each
+/// live timeout's current deadline, plus a `BinaryHeap` of every deadline
+/// ever scheduled for it. Stale heap entries are silently dropped when
+/// popped: one removed by an `Unset` has no map entry left, and one
+/// superseded by a later `Reset` no longer matches the map entry's deadline
+/// and generation.
+pub struct TimerService<T, S> {
+    egress: S,
+    pending: HashMap<T, (Instant, u64)>,
+    heap: BinaryHeap<(Reverse<Instant>, T, u64)>,
+}
+
+impl<T, S> TimerService<T, S> {
+    pub fn new(egress: S) -> Self {
+        Self {
+            egress,
+            pending: HashMap::new(),
+            heap: BinaryHeap::new(),
+        }
+    }
+}
+
+impl<T, S> State<Timeout<T>> for TimerService<T, S>
+where
+    T: Deadline + Eq + Hash + Ord + Clone,
+{
+    fn update(&mut self, message: Timeout<T>) {
+        match message {
+            Timeout::Set(timeout) => {
+                let deadline = Instant::now() + timeout.duration();
+                self.heap.push((Reverse(deadline), timeout.clone(), 0));
+                self.pending.insert(timeout, (deadline, 0));
+            }
+            Timeout::Reset(timeout) => {
+                let generation = self
+                    .pending
+                    .get(&timeout)
+                    .map(|&(_, generation)| generation + 1)
+                    .unwrap_or_default();
+                let deadline = Instant::now() + timeout.duration();
+                self.heap.push((Reverse(deadline), timeout.clone(), generation));
+                self.pending.insert(timeout, (deadline, generation));
+            }
+            Timeout::Unset(timeout) => {
+                self.pending.remove(&timeout);
+            }
+        }
+    }
+}
+
+impl<T, S> TimerService<T, S>
+where
+    T: Deadline + Eq + Hash + Ord + Clone,
+    S: State<T>,
+{
+    /// Drives the service: consumes `Set`/`Reset`/`Unset` messages from
+    /// `drive` and, concurrently, sleeps until the next scheduled deadline
+    /// so a freshly inserted earlier deadline always interrupts the current
+    /// sleep (the `select!` future is rebuilt from the heap's current top on
+    /// every iteration).
+    pub async fn run(&mut self, mut drive: Drive<Timeout<T>>) {
+        loop {
+            let next_deadline = self.heap.peek().map(|&(Reverse(deadline), ..)| deadline);
+            select! {
+                message = drive.recv() => {
+                    let Some(message) = message else {
+                        break;
+                    };
+                    self.update(message);
+                }
+                _ = sleep_until(next_deadline.unwrap_or_else(Instant::now)),
+                    if next_deadline.is_some() =>
+                {
+                    let (Reverse(deadline), timeout, generation) = self.heap.pop().unwrap();
+                    let still_live = matches!(
+                        self.pending.get(&timeout),
+                        Some(&(pending_deadline, pending_generation))
+                            if pending_deadline == deadline && pending_generation == generation
+                    );
+                    if still_live {
+                        self.egress.update(timeout);
+                    }
+                }
+            }
+        }
+    }
+}